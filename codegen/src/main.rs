@@ -4,120 +4,9 @@ use std::fs::File;
 use std::io::{self, Write};
 use std::path::Path;
 
-use serde::Deserialize;
-
-#[derive(Deserialize)]
-struct Db {
-    encodings: BTreeMap<String, Encoding>,
-    profiles: BTreeMap<String, Profile>,
-}
-
-#[derive(Deserialize)]
-struct Encoding {
-    name: String,
-    // iconv: Option<String>,
-    // python_encode: Option<String>,
-    #[serde(deserialize_with = "deserialize_encoding_data", default)]
-    data: Option<Box<[char; 128]>>,
-    notes: Option<String>,
-}
-
-fn deserialize_encoding_data<'de, D>(deserializer: D) -> Result<Option<Box<[char; 128]>>, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    let data: Option<[String; 8]> = serde::Deserialize::deserialize(deserializer)?;
-    let Some(data) = data else { return Ok(None) };
-    let mut vec = Vec::with_capacity(128);
-    vec.extend(data.iter().flat_map(|s| s.chars()));
-    let len = vec.len();
-    vec.into_boxed_slice().try_into().map(Some).map_err(|_| {
-        serde::de::Error::invalid_length(len, &"an array of 8 strings with 16 characters each")
-    })
-}
-
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct Profile {
-    code_pages: BTreeMap<u8, String>,
-    colors: BTreeMap<u8, String>,
-    features: BTreeMap<String, bool>,
-    fonts: BTreeMap<u8, FontInfo>,
-    media: Media,
-    name: String,
-    notes: String,
-    vendor: String,
-}
-
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-enum Color {
-    Black,
-    Red,
-    Alternate,
-}
-
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-#[allow(unused)]
-struct FontInfo {
-    columns: u8,
-}
-
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct Media {
-    dpi: Option<MaybeUnknown<u16>>,
-    width: Width,
-}
-
-impl Media {
-    fn get_width(&self) -> Option<(f32, u16)> {
-        match (self.width.mm.opt(), flatten(self.width.pixels)) {
-            (Some(mm), Some(px)) => Some((mm, px)),
-            (None, None) => None,
-            (Some(mm), None) => {
-                let dpi = flatten(self.dpi).unwrap();
-                let px = f32::from(dpi) * mm * 25.4;
-                Some((mm, px as u16))
-            }
-            (None, Some(px)) => {
-                let dpi = flatten(self.dpi).unwrap();
-                let dpmm = f32::from(dpi) / 25.4;
-                let mm = f32::from(px) / dpmm;
-                Some((mm, px as u16))
-            }
-        }
-    }
-}
-
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-
-struct Width {
-    mm: MaybeUnknown<f32>,
-    pixels: Option<MaybeUnknown<u16>>,
-}
-
-#[derive(Deserialize, Copy, Clone, Debug)]
-enum MaybeUnknown<T> {
-    Unknown,
-    #[serde(untagged)]
-    Known(T),
-}
-
-impl<T> MaybeUnknown<T> {
-    fn opt(self) -> Option<T> {
-        match self {
-            MaybeUnknown::Unknown => None,
-            MaybeUnknown::Known(x) => Some(x),
-        }
-    }
-}
-
-fn flatten<T>(x: Option<MaybeUnknown<T>>) -> Option<T> {
-    x.and_then(|x| x.opt())
-}
+// The deserialization schema is shared with the runtime loader so that the
+// build step and `Db::parse` stay in lock-step.
+use escpos_db::schema::{flatten, Db};
 
 fn write_map<T, W: io::Write>(
     file: &mut W,
@@ -150,7 +39,7 @@ fn main() -> io::Result<()> {
     let encodings = db
         .encodings
         .into_iter()
-        .map(|(k, v)| (heck::AsShoutySnakeCase(k), v))
+        .map(|(k, v)| (heck::AsShoutySnakeCase(k.clone()).to_string(), k, v))
         .collect::<Vec<_>>();
 
     writeln!(file, "#[derive(Debug, Copy, Clone)]")?;
@@ -158,7 +47,7 @@ fn main() -> io::Result<()> {
     writeln!(file, "#[allow(non_camel_case_types)]")?;
     writeln!(file, "/// A code page supported by ESC/POS printers.")?;
     writeln!(file, "pub enum Encoding {{")?;
-    for (name, enc) in &encodings {
+    for (name, _, enc) in &encodings {
         let mut doc = enc.name.clone();
         if let Some(notes) = &enc.notes {
             doc.push_str("\n\n");
@@ -173,7 +62,7 @@ fn main() -> io::Result<()> {
         "pub(crate) fn encoding_data(enc: Encoding) -> Option<&'static [char; 128]> {{"
     )?;
     writeln!(file, "    match enc {{")?;
-    for (name, enc) in &encodings {
+    for (name, _, enc) in &encodings {
         if let Some(data) = &enc.data {
             writeln!(file, "        Encoding::{name} => Some(&{data:?}),",)?;
         }
@@ -182,6 +71,53 @@ fn main() -> io::Result<()> {
     writeln!(file, "    }}")?;
     writeln!(file, "}}")?;
 
+    // A name -> `Encoding` resolver, keyed on the encoding's database id, its
+    // human-readable name, and its iconv / Python codec aliases. Duplicate
+    // spellings are de-duplicated (last definition wins) so `phf` does not
+    // reject the map.
+    let mut aliases = BTreeMap::new();
+    for (variant, key, enc) in &encodings {
+        let value = format!("Encoding::{variant}");
+        let names = [
+            Some(key.as_str()),
+            Some(enc.name.as_str()),
+            enc.iconv.as_deref(),
+            enc.python_encode.as_deref(),
+        ];
+        for name in names.into_iter().flatten() {
+            aliases.insert(name.to_owned(), value.clone());
+        }
+    }
+    let mut map = phf_codegen::Map::new();
+    for (name, value) in &aliases {
+        map.entry(name.as_str(), value);
+    }
+    writeln!(
+        file,
+        "pub(crate) static ENCODING_BY_NAME: phf::Map<&'static str, Encoding> = {};",
+        map.build()
+    )?;
+
+    write!(file, "static ALL_ENCODINGS: &[Encoding] = &[")?;
+    for (variant, _, _) in &encodings {
+        write!(file, "Encoding::{variant}, ")?;
+    }
+    writeln!(file, "];")?;
+
+    writeln!(file, "impl Encoding {{")?;
+    writeln!(
+        file,
+        "    /// Look up an encoding by its name or an iconv / Python codec alias."
+    )?;
+    writeln!(file, "    pub fn from_name(name: &str) -> Option<Encoding> {{")?;
+    writeln!(file, "        ENCODING_BY_NAME.get(name).copied()")?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "    /// An iterator over every [`Encoding`] variant.")?;
+    writeln!(file, "    pub fn all() -> impl Iterator<Item = Encoding> {{")?;
+    writeln!(file, "        ALL_ENCODINGS.iter().copied()")?;
+    writeln!(file, "    }}")?;
+    writeln!(file, "}}")?;
+
     writeln!(
         file,
         "bitflags::bitflags! {{ #[derive(Copy, Clone, Debug, Default)] pub(crate) struct FeaturesInner: u32 {{"
@@ -193,6 +129,21 @@ fn main() -> io::Result<()> {
     }
     writeln!(file, "}} }}")?;
 
+    // A private name -> flag resolver used by the runtime loader to apply the
+    // boolean feature map from `capabilities.json`.
+    writeln!(
+        file,
+        "pub(crate) fn feature_flag_by_name(name: &str) -> Option<FeaturesInner> {{"
+    )?;
+    writeln!(file, "    Some(match name {{")?;
+    for feature in first_profile.features.keys() {
+        let flag_name = heck::AsShoutySnakeCase(feature);
+        writeln!(file, "        {feature:?} => FeaturesInner::{flag_name},")?;
+    }
+    writeln!(file, "        _ => return None,")?;
+    writeln!(file, "    }})")?;
+    writeln!(file, "}}")?;
+
     writeln!(file, "impl Features {{")?;
     for feature in first_profile.features.keys() {
         let fn_name = heck::AsSnakeCase(feature);
@@ -256,7 +207,7 @@ fn main() -> io::Result<()> {
 
         write!(file, "    fonts: ")?;
         write_map(&mut file, &profile.fonts, |file, font| {
-            write!(file, "{font:?}")
+            write!(file, "FontInfo {{ columns: {} }}", font.columns)
         })?;
         writeln!(file, ",")?;
 
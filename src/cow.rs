@@ -0,0 +1,31 @@
+use core::fmt;
+use core::ops::Deref;
+
+/// A borrowed-only stand-in for [`alloc::borrow::Cow`], used when the `alloc`
+/// feature is disabled.
+///
+/// The statically-generated profiles only ever hold `Borrowed` data, so this
+/// lets [`Profile`](crate::Profile) compile with zero allocation and without
+/// requiring an `IntMap: ToOwned` impl. Enabling `alloc` replaces this with the
+/// real `Cow`, which additionally supports owned values.
+pub enum Cow<'a, B: ?Sized + 'a> {
+    /// Borrowed data.
+    Borrowed(&'a B),
+}
+
+impl<B: ?Sized> Deref for Cow<'_, B> {
+    type Target = B;
+    fn deref(&self) -> &B {
+        match self {
+            Cow::Borrowed(b) => b,
+        }
+    }
+}
+
+impl<B: ?Sized + fmt::Debug> fmt::Debug for Cow<'_, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cow::Borrowed(b) => fmt::Debug::fmt(b, f),
+        }
+    }
+}
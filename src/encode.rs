@@ -0,0 +1,132 @@
+//! Encoding text into an ESC/POS byte stream with automatic code-page switching.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+use crate::{Encoding, Profile};
+
+/// What to do when a character cannot be represented by any of a profile's
+/// code pages.
+#[derive(Copy, Clone, Debug)]
+pub enum Fallback {
+    /// Emit this byte in place of the character.
+    Byte(u8),
+    /// Abort encoding and return an [`EncodeError`].
+    Error,
+}
+
+impl Default for Fallback {
+    fn default() -> Self {
+        Fallback::Byte(b'?')
+    }
+}
+
+/// An error returned when a character cannot be encoded and the [`Fallback`] is
+/// set to [`Fallback::Error`].
+#[derive(Copy, Clone, Debug)]
+pub struct EncodeError {
+    /// The character that could not be represented.
+    pub ch: char,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no code page represents the character {:?}", self.ch)
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// Encodes text into the ESC/POS byte stream for a particular [`Profile`].
+///
+/// ASCII characters are emitted directly; for anything else the encoder selects
+/// a code page from [`Profile::code_pages`] that can represent the character,
+/// emitting the `ESC t n` code-page-select command (`0x1B 0x74 n`) only when a
+/// switch is actually required.
+#[derive(Copy, Clone, Debug)]
+pub struct Encoder<'a> {
+    profile: &'a Profile<'a>,
+    fallback: Fallback,
+}
+
+impl<'a> Encoder<'a> {
+    /// Create an encoder for `profile`, replacing unrepresentable characters
+    /// with `b'?'`.
+    pub fn new(profile: &'a Profile<'a>) -> Self {
+        Self {
+            profile,
+            fallback: Fallback::default(),
+        }
+    }
+
+    /// Set the behavior for characters no code page can represent.
+    pub fn with_fallback(mut self, fallback: Fallback) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Encode `s` into the bytes a printer using this profile should receive.
+    pub fn encode(&self, s: &str) -> Result<Vec<u8>, EncodeError> {
+        let mut out = Vec::new();
+        // The code-page key currently selected on the printer, if any.
+        let mut selected: Option<u8> = None;
+        for c in s.chars() {
+            if (c as u32) < 0x80 {
+                out.push(c as u8);
+                continue;
+            }
+
+            // Fast path: the character fits in the currently-selected page.
+            if let Some(key) = selected {
+                if let Some(enc) = self.profile.code_pages.get(key) {
+                    if let Some(idx) = reverse_index(*enc, c) {
+                        out.push(0x80 + idx);
+                        continue;
+                    }
+                }
+            }
+
+            // Otherwise find a page that can represent it and switch to it.
+            let found = self
+                .profile
+                .code_pages
+                .iter()
+                .find_map(|(key, enc)| reverse_index(*enc, c).map(|idx| (key, idx)));
+            match found {
+                Some((key, idx)) => {
+                    out.extend_from_slice(&[0x1b, 0x74, key]);
+                    selected = Some(key);
+                    out.push(0x80 + idx);
+                }
+                None => match self.fallback {
+                    Fallback::Byte(b) => out.push(b),
+                    Fallback::Error => return Err(EncodeError { ch: c }),
+                },
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Look up the table index of `c` within `enc`, such that the printer byte is
+/// `0x80 + index`.
+///
+/// The reverse `char -> index` map for each encoding is built once and cached,
+/// so repeated encodes are O(1) per character.
+fn reverse_index(enc: Encoding, c: char) -> Option<u8> {
+    let data = enc.data()?;
+    static CACHE: OnceLock<Mutex<HashMap<usize, HashMap<char, u8>>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut cache = cache.lock().unwrap();
+    // The data tables are `'static`, so their address uniquely identifies one.
+    let map = cache.entry(data.as_ptr() as usize).or_insert_with(|| {
+        let mut map = HashMap::with_capacity(data.len());
+        for (i, &ch) in data.iter().enumerate() {
+            // Keep the first index for a character that appears more than once.
+            map.entry(ch).or_insert(i as u8);
+        }
+        map
+    });
+    map.get(&c).copied()
+}
@@ -1,6 +1,9 @@
-use std::borrow::Borrow;
-use std::ops::Deref;
-use std::{fmt, mem};
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::ToOwned, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::{borrow::Borrow, mem, ops::Deref};
 
 /// A mapping from a `u8` to `T`.
 ///
@@ -96,7 +99,7 @@ impl<'a, T> IntoIterator for &'a IntMap<T> {
 
 /// An iterator over an [`IntMap`].
 pub struct IntMapIter<'a, T> {
-    inner: std::slice::Iter<'a, (u8, T)>,
+    inner: core::slice::Iter<'a, (u8, T)>,
 }
 
 impl<'a, T> Iterator for IntMapIter<'a, T> {
@@ -106,18 +109,25 @@ impl<'a, T> Iterator for IntMapIter<'a, T> {
     }
 }
 
-impl<T> ToOwned for IntMap<T> {
+#[cfg(feature = "alloc")]
+impl<T: Clone> ToOwned for IntMap<T> {
     type Owned = OwnedIntMap<T>;
     fn to_owned(&self) -> Self::Owned {
-        todo!()
+        // `entries` is already sorted and duplicate-free, so it can be copied
+        // verbatim into the owned representation.
+        OwnedIntMap {
+            entries: self.entries.to_vec(),
+        }
     }
 }
 
 /// An owned version of [`IntMap`].
+#[cfg(feature = "alloc")]
 pub struct OwnedIntMap<T> {
     entries: Vec<(u8, T)>,
 }
 
+#[cfg(feature = "alloc")]
 impl<T> Deref for OwnedIntMap<T> {
     type Target = IntMap<T>;
     fn deref(&self) -> &Self::Target {
@@ -125,12 +135,14 @@ impl<T> Deref for OwnedIntMap<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> Borrow<IntMap<T>> for OwnedIntMap<T> {
     fn borrow(&self) -> &IntMap<T> {
         self
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> OwnedIntMap<T> {
     /// Insert a new entry into the map, returning the previous value at `key` if it existed.
     pub fn insert(&mut self, key: u8, val: T) -> Option<T> {
@@ -144,12 +156,14 @@ impl<T> OwnedIntMap<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T: fmt::Debug> fmt::Debug for OwnedIntMap<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         (**self).fmt(f)
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> FromIterator<(u8, T)> for OwnedIntMap<T> {
     fn from_iter<I: IntoIterator<Item = (u8, T)>>(iter: I) -> Self {
         let mut entries = iter.into_iter().collect::<Vec<_>>();
@@ -158,6 +172,7 @@ impl<T> FromIterator<(u8, T)> for OwnedIntMap<T> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> Extend<(u8, T)> for OwnedIntMap<T> {
     fn extend<I: IntoIterator<Item = (u8, T)>>(&mut self, iter: I) {
         struct DropGuard<'a, T> {
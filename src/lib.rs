@@ -1,15 +1,34 @@
 //! Rust bindings to the [ESC/POS printer database](https://github.com/receipt-print-hq/escpos-printer-db).
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs)]
 
-use std::borrow::Cow;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+#[cfg(not(feature = "alloc"))]
+pub use crate::cow::Cow;
 
 #[rustfmt::skip]
 mod gen;
+#[cfg(not(feature = "alloc"))]
+mod cow;
 mod int_map;
-
+#[cfg(feature = "std")]
+mod encode;
+#[cfg(feature = "alloc")]
+mod raster;
+#[cfg(feature = "serde")]
+pub mod schema;
+
+#[cfg(feature = "std")]
+pub use crate::encode::*;
 pub use crate::gen::*;
 pub use crate::int_map::*;
+#[cfg(feature = "alloc")]
+pub use crate::raster::*;
 
 impl Encoding {
     /// This encoding's 7-bit codepage.
@@ -98,7 +117,7 @@ pub enum Color {
 }
 
 /// Information for a supported ESC/POS font.
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug)]
 #[non_exhaustive]
 pub struct FontInfo {
     /// The maximum number of characters that can fit on a line, using this font.
@@ -110,7 +129,7 @@ pub struct FontInfo {
 pub struct Features(FeaturesInner);
 
 impl Features {
-    const fn _with(mut self, flag: FeaturesInner, on: bool) -> Self {
+    pub(crate) const fn _with(mut self, flag: FeaturesInner, on: bool) -> Self {
         self.0 = if on {
             self.0.union(flag)
         } else {
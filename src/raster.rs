@@ -0,0 +1,198 @@
+//! Raster image command generation for the ESC/POS `GS v 0` command.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::Profile;
+
+/// The maximum number of packed bytes a single `GS v 0` command will carry.
+///
+/// Tall images are split into multiple commands so that one command's payload
+/// stays within the 16-bit length the printer reserves for its receive buffer.
+const MAX_BAND_BYTES: usize = 0xffff;
+
+/// An error produced while building or emitting a raster image.
+#[derive(Copy, Clone, Debug)]
+pub enum RasterError {
+    /// The supplied buffer's length did not match the image dimensions:
+    /// `ceil(width / 8) * height` bytes for packed bits, or `width * height`
+    /// pixels for grayscale.
+    BadDimensions {
+        /// The expected number of elements.
+        expected: usize,
+        /// The number of elements that were supplied.
+        got: usize,
+    },
+    /// The image is wider than the profile's printable width.
+    TooWide {
+        /// The image width in pixels.
+        width: u16,
+        /// The printer's maximum printable width in pixels.
+        max: u16,
+    },
+    /// The profile does not specify a print width.
+    UnknownWidth,
+}
+
+impl fmt::Display for RasterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RasterError::BadDimensions { expected, got } => {
+                write!(f, "expected a buffer of {expected} elements but got {got}")
+            }
+            RasterError::TooWide { width, max } => {
+                write!(f, "image width {width}px exceeds printable width {max}px")
+            }
+            RasterError::UnknownWidth => f.write_str("profile has no print width"),
+        }
+    }
+}
+
+impl core::error::Error for RasterError {}
+
+/// How to reduce an 8-bit grayscale buffer to 1 bit per pixel.
+#[derive(Copy, Clone, Debug)]
+pub enum Dithering {
+    /// Threshold each pixel independently: values `< threshold` are black.
+    Threshold(u8),
+    /// Floyd–Steinberg error diffusion around `threshold`, for photos.
+    FloydSteinberg(u8),
+}
+
+/// A monochrome raster image, stored as packed 1-bpp rows.
+///
+/// Each row is `ceil(width / 8)` bytes, packed MSB-first with bit `1` meaning a
+/// black dot.
+#[derive(Clone, Debug)]
+pub struct RasterImage {
+    width: u16,
+    height: u16,
+    bits: Vec<u8>,
+}
+
+impl RasterImage {
+    /// The number of bytes in each packed row.
+    fn bytes_per_row(&self) -> usize {
+        bytes_per_row(self.width)
+    }
+
+    /// Build an image from a pre-packed 1-bpp bit buffer (MSB-first, bit `1` =
+    /// black).
+    pub fn from_bits(width: u16, height: u16, bits: Vec<u8>) -> Result<Self, RasterError> {
+        let expected = bytes_per_row(width) * usize::from(height);
+        if bits.len() != expected {
+            return Err(RasterError::BadDimensions {
+                expected,
+                got: bits.len(),
+            });
+        }
+        Ok(Self {
+            width,
+            height,
+            bits,
+        })
+    }
+
+    /// Build an image from an 8-bit grayscale buffer (`0` = black, `255` =
+    /// white), reducing it to 1 bit per pixel with `dithering`.
+    pub fn from_gray(
+        width: u16,
+        height: u16,
+        gray: &[u8],
+        dithering: Dithering,
+    ) -> Result<Self, RasterError> {
+        let (w, h) = (usize::from(width), usize::from(height));
+        if gray.len() != w * h {
+            return Err(RasterError::BadDimensions {
+                expected: w * h,
+                got: gray.len(),
+            });
+        }
+
+        let row_bytes = bytes_per_row(width);
+        let mut bits = Vec::new();
+        bits.resize(row_bytes * h, 0u8);
+        let mut set = |x: usize, y: usize| bits[y * row_bytes + x / 8] |= 0x80 >> (x % 8);
+
+        match dithering {
+            Dithering::Threshold(threshold) => {
+                for y in 0..h {
+                    for x in 0..w {
+                        if gray[y * w + x] < threshold {
+                            set(x, y);
+                        }
+                    }
+                }
+            }
+            Dithering::FloydSteinberg(threshold) => {
+                // Diffuse the quantization error into neighbouring pixels.
+                let mut buf: Vec<i16> = gray.iter().map(|&v| i16::from(v)).collect();
+                let mut spread = |buf: &mut [i16], x: usize, y: usize, err: i16, num: i16| {
+                    if x < w && y < h {
+                        buf[y * w + x] = (buf[y * w + x] + err * num / 16).clamp(0, 255);
+                    }
+                };
+                for y in 0..h {
+                    for x in 0..w {
+                        let old = buf[y * w + x];
+                        let black = old < i16::from(threshold);
+                        if black {
+                            set(x, y);
+                        }
+                        let new = if black { 0 } else { 255 };
+                        let err = old - new;
+                        spread(&mut buf, x + 1, y, err, 7);
+                        if x > 0 {
+                            spread(&mut buf, x - 1, y + 1, err, 3);
+                        }
+                        spread(&mut buf, x, y + 1, err, 5);
+                        spread(&mut buf, x + 1, y + 1, err, 1);
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            bits,
+        })
+    }
+
+    /// Emit the `GS v 0` command(s) for this image, checked against `profile`'s
+    /// printable width.
+    ///
+    /// Images taller than a single command can carry are split into multiple
+    /// `GS v 0` blocks.
+    pub fn to_commands(&self, profile: &Profile<'_>) -> Result<Vec<u8>, RasterError> {
+        let max = profile
+            .media
+            .width
+            .as_ref()
+            .ok_or(RasterError::UnknownWidth)?
+            .px;
+        if self.width > max {
+            return Err(RasterError::TooWide {
+                width: self.width,
+                max,
+            });
+        }
+
+        let row_bytes = self.bytes_per_row();
+        let rows_per_band = (MAX_BAND_BYTES / row_bytes.max(1)).max(1);
+        let [xl, xh] = (row_bytes as u16).to_le_bytes();
+
+        let mut out = Vec::new();
+        for band in self.bits.chunks((row_bytes * rows_per_band).max(1)) {
+            let rows = (band.len() / row_bytes) as u16;
+            let [yl, yh] = rows.to_le_bytes();
+            out.extend_from_slice(&[0x1d, 0x76, 0x30, 0x00, xl, xh, yl, yh]);
+            out.extend_from_slice(band);
+        }
+        Ok(out)
+    }
+}
+
+fn bytes_per_row(width: u16) -> usize {
+    usize::from(width).div_ceil(8)
+}
@@ -0,0 +1,243 @@
+//! Deserialization of the [`capabilities.json`] schema, shared between the
+//! `codegen` build step and the runtime profile loader.
+//!
+//! These types mirror the on-disk JSON produced by the upstream
+//! [ESC/POS printer database]. The build script uses them to emit [`gen.rs`],
+//! while [`Db::parse`] and [`Profile::from_json_value`] reuse the exact same
+//! code path to load a newer or vendor-supplied database at runtime.
+//!
+//! This module is only available when the `serde` feature is enabled.
+//!
+//! [`capabilities.json`]: https://github.com/receipt-print-hq/escpos-printer-db/blob/master/dist/capabilities.json
+//! [ESC/POS printer database]: https://github.com/receipt-print-hq/escpos-printer-db
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::gen::feature_flag_by_name;
+use crate::{Color, Features, FontInfo, Media, Profile, Width};
+
+/// The top-level `capabilities.json` database.
+#[derive(Deserialize)]
+pub struct Db {
+    /// The code page definitions, keyed by their database id.
+    pub encodings: BTreeMap<String, Encoding>,
+    /// The printer profiles, keyed by their database id.
+    pub profiles: BTreeMap<String, ProfileDef>,
+}
+
+/// A single code page definition.
+#[derive(Deserialize)]
+pub struct Encoding {
+    /// The human-readable name of this encoding.
+    pub name: String,
+    /// The iconv identifier for this encoding, if any.
+    pub iconv: Option<String>,
+    /// The Python codec name for this encoding, if any.
+    pub python_encode: Option<String>,
+    /// The upper 128 characters of this code page, if known.
+    #[serde(deserialize_with = "deserialize_encoding_data", default)]
+    pub data: Option<Box<[char; 128]>>,
+    /// Free-form notes about this encoding.
+    pub notes: Option<String>,
+}
+
+fn deserialize_encoding_data<'de, D>(deserializer: D) -> Result<Option<Box<[char; 128]>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let data: Option<[String; 8]> = serde::Deserialize::deserialize(deserializer)?;
+    let Some(data) = data else { return Ok(None) };
+    let mut vec = Vec::with_capacity(128);
+    vec.extend(data.iter().flat_map(|s| s.chars()));
+    let len = vec.len();
+    vec.into_boxed_slice().try_into().map(Some).map_err(|_| {
+        serde::de::Error::invalid_length(len, &"an array of 8 strings with 16 characters each")
+    })
+}
+
+/// A printer profile as stored in the database.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileDef {
+    /// The code pages this printer supports, keyed by code-page select value.
+    pub code_pages: BTreeMap<u8, String>,
+    /// The ink colors this printer supports.
+    pub colors: BTreeMap<u8, String>,
+    /// The feature flags this printer supports.
+    pub features: BTreeMap<String, bool>,
+    /// The fonts this printer supports.
+    pub fonts: BTreeMap<u8, FontInfoDef>,
+    /// Print media information.
+    pub media: MediaDef,
+    /// The human-readable name of this printer.
+    pub name: String,
+    /// Free-form notes about this printer.
+    pub notes: String,
+    /// The vendor or manufacturer of this printer.
+    pub vendor: String,
+}
+
+/// Font information as stored in the database.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FontInfoDef {
+    /// The maximum number of characters per line using this font.
+    pub columns: u8,
+}
+
+/// Print media information as stored in the database.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaDef {
+    /// The pixel density of this printer, if known.
+    pub dpi: Option<MaybeUnknown<u16>>,
+    /// The print width of this printer.
+    pub width: WidthDef,
+}
+
+impl MediaDef {
+    /// Reconcile the `mm` and `pixels` width fields, filling in whichever is
+    /// missing from the `dpi`.
+    pub fn get_width(&self) -> Option<(f32, u16)> {
+        match (self.width.mm.opt(), flatten(self.width.pixels)) {
+            (Some(mm), Some(px)) => Some((mm, px)),
+            (None, None) => None,
+            (Some(mm), None) => {
+                // There are 25.4 mm per inch, so divide to go from mm to dots.
+                let dpi = flatten(self.dpi).unwrap();
+                let px = mm * f32::from(dpi) / 25.4;
+                Some((mm, px as u16))
+            }
+            (None, Some(px)) => {
+                let dpi = flatten(self.dpi).unwrap();
+                let mm = f32::from(px) * 25.4 / f32::from(dpi);
+                Some((mm, px))
+            }
+        }
+    }
+}
+
+/// The print width as stored in the database.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WidthDef {
+    /// The print width in millimeters.
+    pub mm: MaybeUnknown<f32>,
+    /// The print width in pixels.
+    pub pixels: Option<MaybeUnknown<u16>>,
+}
+
+/// A value that may be present, absent, or explicitly `"unknown"` in the JSON.
+#[derive(Deserialize, Copy, Clone, Debug)]
+pub enum MaybeUnknown<T> {
+    /// The value is explicitly unknown.
+    Unknown,
+    /// The value is known.
+    #[serde(untagged)]
+    Known(T),
+}
+
+impl<T> MaybeUnknown<T> {
+    /// Convert to an `Option`, mapping `Unknown` to `None`.
+    pub fn opt(self) -> Option<T> {
+        match self {
+            MaybeUnknown::Unknown => None,
+            MaybeUnknown::Known(x) => Some(x),
+        }
+    }
+}
+
+/// Flatten a possibly-absent [`MaybeUnknown`] into an `Option`.
+pub fn flatten<T>(x: Option<MaybeUnknown<T>>) -> Option<T> {
+    x.and_then(|x| x.opt())
+}
+
+impl ProfileDef {
+    /// Build an owning [`Profile`] from this definition.
+    ///
+    /// Code pages whose name is not present in this crate's generated
+    /// [`Encoding`](crate::Encoding) enum, and feature flags this crate does
+    /// not know about, are silently skipped.
+    fn to_profile(&self) -> Profile<'static> {
+        let mut features = Features::new();
+        for (name, &on) in &self.features {
+            if let Some(flag) = feature_flag_by_name(name) {
+                features = features._with(flag, on);
+            }
+        }
+
+        let code_pages = self
+            .code_pages
+            .iter()
+            .filter_map(|(&k, name)| Some((k, crate::Encoding::from_name(name)?)))
+            .collect::<crate::OwnedIntMap<_>>();
+
+        let colors = self
+            .colors
+            .iter()
+            .filter_map(|(&k, name)| Some((k, color_from_name(name)?)))
+            .collect::<crate::OwnedIntMap<_>>();
+
+        let fonts = self
+            .fonts
+            .iter()
+            .map(|(&k, font)| (k, FontInfo { columns: font.columns }))
+            .collect::<crate::OwnedIntMap<_>>();
+
+        let media = Media::new(
+            flatten(self.media.dpi),
+            self.media
+                .get_width()
+                .map(|(mm, px)| Width::new(mm, px)),
+        );
+
+        Profile {
+            name: Cow::Owned(self.name.clone()),
+            vendor: Cow::Owned(self.vendor.clone()),
+            features,
+            code_pages: Cow::Owned(code_pages),
+            colors: Cow::Owned(colors),
+            fonts: Cow::Owned(fonts),
+            media,
+        }
+    }
+}
+
+fn color_from_name(name: &str) -> Option<Color> {
+    Some(match name {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "alternate" => Color::Alternate,
+        _ => return None,
+    })
+}
+
+impl Db {
+    /// Parse a database from a `capabilities.json` string.
+    pub fn parse(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Consume this database, yielding an owning [`Profile`] for each entry
+    /// keyed by its database id.
+    ///
+    /// The profiles can be merged with [`ALL_PROFILES`](crate::ALL_PROFILES) to
+    /// extend the statically-generated database at runtime.
+    pub fn into_profiles(self) -> impl Iterator<Item = (String, Profile<'static>)> {
+        self.profiles
+            .into_iter()
+            .map(|(id, profile)| (id, profile.to_profile()))
+    }
+}
+
+impl Profile<'static> {
+    /// Build an owning profile from a single deserialized `capabilities.json`
+    /// profile value.
+    pub fn from_json_value(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        let profile: ProfileDef = serde_json::from_value(value)?;
+        Ok(profile.to_profile())
+    }
+}